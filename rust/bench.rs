@@ -1,10 +1,24 @@
 extern crate serde;
 use serde::Serialize;
 use std::{
-    sync::{Arc, Mutex},
+    collections::HashMap,
+    hash::BuildHasher,
+    sync::{Arc, Mutex, RwLock},
     time::Instant,
 };
 
+#[cfg(feature = "dashmap")]
+use dashmap::DashMap;
+
+#[cfg(feature = "fxhash")]
+use rustc_hash::FxBuildHasher;
+
+#[cfg(feature = "ahash")]
+use ahash::RandomState as AHashState;
+
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+
 #[derive(Serialize)]
 struct ResultOut {
     runtime: String,
@@ -14,8 +28,103 @@ struct ResultOut {
     keys: usize,
     read_ratio: f64,
     seed: u64,
-    duration_ms: u128,
     rss_bytes: u64,
+    shards: usize,
+    shard_align_bytes: usize,
+    hasher: String,
+    runs: usize,
+    duration_ms_min: u128,
+    duration_ms_mean: f64,
+    duration_ms_max: u128,
+    duration_ms_p50: u128,
+    duration_ms_p99: u128,
+    input_path: String,
+    total_lines: usize,
+}
+
+// The CLI-parsed parameters for a single benchmark invocation. Bundled into
+// one struct (rather than threaded through every `run_*`/`dispatch` call as
+// positional args) so adding a flag doesn't mean touching every call site.
+struct RunConfig {
+    model: String,
+    threads: usize,
+    iterations: usize,
+    keys: usize,
+    read_ratio: f64,
+    seed: u64,
+    shards: usize,
+    hasher_name: String,
+    warmup: usize,
+    runs: usize,
+    input_path: Option<String>,
+}
+
+// Sorts `durations` and reports min/mean/max plus the p50/p99 percentile,
+// indexed at `ceil(p * (n - 1))` as is standard for small samples.
+fn duration_stats(durations: &[u128]) -> (u128, f64, u128, u128, u128) {
+    let mut sorted = durations.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    let percentile = |p: f64| -> u128 {
+        let idx = (p * (n - 1) as f64).ceil() as usize;
+        sorted[idx.min(n - 1)]
+    };
+    let mean = sorted.iter().sum::<u128>() as f64 / n as f64;
+    (sorted[0], mean, sorted[n - 1], percentile(0.50), percentile(0.99))
+}
+
+// Pads `T` out to a full cache line so adjacent shards never share one,
+// mirroring the `CacheAligned<T>` rustc uses inside its `Sharded<T>` map.
+#[repr(align(64))]
+struct CacheAligned<T>(T);
+
+impl<T> std::ops::Deref for CacheAligned<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for CacheAligned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// Per-shard map types used by the Mutex/RwLock sharded backends, named so
+// the `Vec<...>` storing them doesn't nest deep enough to trip clippy's
+// `type_complexity` lint.
+type ShardedMutexMap<S> = Arc<CacheAligned<Mutex<HashMap<usize, i64, S>>>>;
+type ShardedRwLockMap<S> = Arc<CacheAligned<RwLock<HashMap<usize, i64, S>>>>;
+
+// Picks the shard from the top `shard_bits` bits of the hash instead of
+// `hash % shard_count`, avoiding a modulo in the hot loop.
+fn shard_of(hash: u64, shard_bits: u32) -> usize {
+    if shard_bits == 0 {
+        0
+    } else {
+        (hash >> (64 - shard_bits)) as usize
+    }
+}
+
+fn hash_key(key: usize) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut h);
+    h.finish()
+}
+
+// Forces the shard count to a power of two (rounding up) so the shard index
+// can be taken from the hash's top bits with a shift instead of a modulo.
+// When the caller didn't pass `--shards`, defaults to `num_cpus * 4`.
+fn effective_shard_count(requested: Option<usize>) -> usize {
+    let n = requested.unwrap_or_else(|| {
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        cpus * 4
+    });
+    n.next_power_of_two()
 }
 
 fn rss_bytes() -> u64 {
@@ -33,7 +142,7 @@ fn rss_bytes() -> u64 {
 
 fn splitmix64(mut x: u64) -> impl FnMut() -> u64 {
     move || {
-        x = x.wrapping_add(0x9E3779B97f4A7C15);
+        x = x.wrapping_add(0x9E3779B97F4A7C15);
         let mut z = x;
         z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
         z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
@@ -41,73 +150,466 @@ fn splitmix64(mut x: u64) -> impl FnMut() -> u64 {
     }
 }
 
-fn run_sharded(
-    threads: usize,
+fn make_result(
+    cfg: &RunConfig,
+    model: &str,
+    hasher: &str,
     iterations: usize,
-    keys: usize,
-    read_ratio: f64,
-    seed: u64,
-    shards_n: usize,
-) {
-    let mut shards = Vec::with_capacity(shards_n);
-    for _ in 0..shards_n {
-        shards.push(Arc::new(Mutex::new(vec![0i64; 0])));
-    }
-    // store as Vec of hashmaps but for speed, use Vec with capacity keys/shard and sparse fill via map-like approach
-    let mut maps: Vec<Arc<Mutex<std::collections::HashMap<usize, i64>>>> =
-        Vec::with_capacity(shards_n);
-    for _ in 0..shards_n {
-        maps.push(Arc::new(Mutex::new(
-            std::collections::HashMap::with_capacity(keys / shards_n + 1),
-        )));
-    }
-    for i in 0..keys {
-        let s = &maps[i % shards_n];
-        let mut g = s.lock().unwrap();
-        g.insert(i, 0);
-    }
-    let per = iterations / threads;
-    let start = Instant::now();
-    let mut handles = Vec::new();
-    for t in 0..threads {
-        let maps = maps.clone();
-        let reads = (read_ratio * 1000.0) as u64;
-        let mut rnd = splitmix64(seed + t as u64);
-        handles.push(std::thread::spawn(move || {
-            for _ in 0..per {
-                let k = (rnd() % keys as u64) as usize;
-                let r = rnd() % 1000;
-                let sidx = k % maps.len();
-                let m = &maps[sidx];
-                if r < reads {
-                    let g = m.lock().unwrap();
-                    let _ = g.get(&k);
-                } else {
-                    let mut g = m.lock().unwrap();
-                    let e = g.entry(k).or_insert(0);
-                    *e += 1;
-                }
+    durations: &[u128],
+    shard_align_bytes: usize,
+    total_lines: usize,
+) -> ResultOut {
+    let (min, mean, max, p50, p99) = duration_stats(durations);
+    ResultOut {
+        runtime: format!("rustc{}", rustc_version_runtime()),
+        model: model.to_string(),
+        threads: cfg.threads,
+        iterations,
+        keys: cfg.keys,
+        read_ratio: cfg.read_ratio,
+        seed: cfg.seed,
+        rss_bytes: rss_bytes(),
+        shards: cfg.shards,
+        shard_align_bytes,
+        hasher: hasher.to_string(),
+        runs: durations.len(),
+        duration_ms_min: min,
+        duration_ms_mean: mean,
+        duration_ms_max: max,
+        duration_ms_p50: p50,
+        duration_ms_p99: p99,
+        input_path: cfg.input_path.clone().unwrap_or_default(),
+        total_lines,
+    }
+}
+
+// One Mutex<HashMap> shared by every thread. The worst case for contention,
+// but useful as the baseline the sharded/lock-free models are measured against.
+fn run_global_mutex(cfg: &RunConfig) {
+    let per = cfg.iterations / cfg.threads;
+    let mut durations = Vec::with_capacity(cfg.runs);
+    for rep in 0..(cfg.warmup + cfg.runs) {
+        let map = Arc::new(Mutex::new(HashMap::with_capacity(cfg.keys)));
+        {
+            let mut g = map.lock().unwrap();
+            for i in 0..cfg.keys {
+                g.insert(i, 0i64);
             }
-        }));
+        }
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for t in 0..cfg.threads {
+            let map = map.clone();
+            let keys = cfg.keys;
+            let reads = (cfg.read_ratio * 1000.0) as u64;
+            let mut rnd = splitmix64(cfg.seed + t as u64);
+            handles.push(std::thread::spawn(move || {
+                for _ in 0..per {
+                    let k = (rnd() % keys as u64) as usize;
+                    let r = rnd() % 1000;
+                    if r < reads {
+                        let g = map.lock().unwrap();
+                        let _ = g.get(&k);
+                    } else {
+                        let mut g = map.lock().unwrap();
+                        let e = g.entry(k).or_insert(0);
+                        *e += 1;
+                    }
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        if rep >= cfg.warmup {
+            durations.push(start.elapsed().as_millis());
+        }
     }
-    for h in handles {
-        h.join().unwrap();
+    let out = make_result(cfg, "global-mutex", "siphash", per * cfg.threads, &durations, 0, 0);
+    println!("{}", serde_json::to_string(&out).unwrap());
+}
+
+// Per-shard, cache-line-aligned Mutex<HashMap<_, _, S>>. Shard count is
+// forced to a power of two and the shard is picked from the hash's top bits
+// (see `shard_of`) instead of a modulo, so this is the original (and still
+// default) model minus the false sharing and modulo cost. `S` is the
+// per-map `BuildHasher`, selected by `--hasher`, so the measurement can
+// separate hashing cost from locking cost.
+fn run_sharded_mutex<S: BuildHasher + Default + Send + Sync + 'static>(cfg: &RunConfig) {
+    let shard_bits = cfg.shards.trailing_zeros();
+    let per = cfg.iterations / cfg.threads;
+    let mut durations = Vec::with_capacity(cfg.runs);
+    for rep in 0..(cfg.warmup + cfg.runs) {
+        let mut maps: Vec<ShardedMutexMap<S>> = Vec::with_capacity(cfg.shards);
+        for _ in 0..cfg.shards {
+            maps.push(Arc::new(CacheAligned(Mutex::new(HashMap::with_capacity_and_hasher(
+                cfg.keys / cfg.shards + 1,
+                S::default(),
+            )))));
+        }
+        for i in 0..cfg.keys {
+            let sidx = shard_of(hash_key(i), shard_bits);
+            let mut g = maps[sidx].lock().unwrap();
+            g.insert(i, 0);
+        }
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for t in 0..cfg.threads {
+            let maps = maps.clone();
+            let keys = cfg.keys;
+            let reads = (cfg.read_ratio * 1000.0) as u64;
+            let mut rnd = splitmix64(cfg.seed + t as u64);
+            handles.push(std::thread::spawn(move || {
+                for _ in 0..per {
+                    let k = (rnd() % keys as u64) as usize;
+                    let r = rnd() % 1000;
+                    let sidx = shard_of(hash_key(k), shard_bits);
+                    let m = &maps[sidx];
+                    if r < reads {
+                        let g = m.lock().unwrap();
+                        let _ = g.get(&k);
+                    } else {
+                        let mut g = m.lock().unwrap();
+                        let e = g.entry(k).or_insert(0);
+                        *e += 1;
+                    }
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        if rep >= cfg.warmup {
+            durations.push(start.elapsed().as_millis());
+        }
     }
-    let dur = start.elapsed().as_millis();
-    let out = ResultOut {
-        runtime: format!("rustc{}", rustc_version_runtime()),
-        model: "threads-sharded".to_string(),
-        threads,
-        iterations: per * threads,
-        keys,
-        read_ratio,
-        seed,
-        duration_ms: dur,
-        rss_bytes: rss_bytes(),
+    let out = make_result(
+        cfg,
+        "threads-sharded",
+        &cfg.hasher_name,
+        per * cfg.threads,
+        &durations,
+        64,
+        0,
+    );
+    println!("{}", serde_json::to_string(&out).unwrap());
+}
+
+// Same cache-line-aligned, power-of-two sharding as `run_sharded_mutex`, but
+// each shard is an RwLock so the 0.9-read-ratio default workload takes
+// shared read guards instead of contending on an exclusive Mutex for every
+// get(). `S` is the per-map `BuildHasher`, selected by `--hasher`.
+fn run_sharded_rwlock<S: BuildHasher + Default + Send + Sync + 'static>(cfg: &RunConfig) {
+    let shard_bits = cfg.shards.trailing_zeros();
+    let per = cfg.iterations / cfg.threads;
+    let mut durations = Vec::with_capacity(cfg.runs);
+    for rep in 0..(cfg.warmup + cfg.runs) {
+        let mut maps: Vec<ShardedRwLockMap<S>> = Vec::with_capacity(cfg.shards);
+        for _ in 0..cfg.shards {
+            maps.push(Arc::new(CacheAligned(RwLock::new(HashMap::with_capacity_and_hasher(
+                cfg.keys / cfg.shards + 1,
+                S::default(),
+            )))));
+        }
+        for i in 0..cfg.keys {
+            let sidx = shard_of(hash_key(i), shard_bits);
+            let mut g = maps[sidx].write().unwrap();
+            g.insert(i, 0);
+        }
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for t in 0..cfg.threads {
+            let maps = maps.clone();
+            let keys = cfg.keys;
+            let reads = (cfg.read_ratio * 1000.0) as u64;
+            let mut rnd = splitmix64(cfg.seed + t as u64);
+            handles.push(std::thread::spawn(move || {
+                for _ in 0..per {
+                    let k = (rnd() % keys as u64) as usize;
+                    let r = rnd() % 1000;
+                    let sidx = shard_of(hash_key(k), shard_bits);
+                    let m = &maps[sidx];
+                    if r < reads {
+                        let g = m.read().unwrap();
+                        let _ = g.get(&k);
+                    } else {
+                        let mut g = m.write().unwrap();
+                        let e = g.entry(k).or_insert(0);
+                        *e += 1;
+                    }
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        if rep >= cfg.warmup {
+            durations.push(start.elapsed().as_millis());
+        }
+    }
+    let out = make_result(
+        cfg,
+        "sharded-rwlock",
+        &cfg.hasher_name,
+        per * cfg.threads,
+        &durations,
+        64,
+        0,
+    );
+    println!("{}", serde_json::to_string(&out).unwrap());
+}
+
+// Lock-free(-ish) sharded map from the `dashmap` crate. Only built when the
+// `dashmap` feature is enabled, so the default build has no extra dependency.
+#[cfg(feature = "dashmap")]
+fn run_dashmap(cfg: &RunConfig) {
+    let per = cfg.iterations / cfg.threads;
+    let mut durations = Vec::with_capacity(cfg.runs);
+    for rep in 0..(cfg.warmup + cfg.runs) {
+        let map = Arc::new(DashMap::with_capacity(cfg.keys));
+        for i in 0..cfg.keys {
+            map.insert(i, 0i64);
+        }
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for t in 0..cfg.threads {
+            let map = map.clone();
+            let keys = cfg.keys;
+            let reads = (cfg.read_ratio * 1000.0) as u64;
+            let mut rnd = splitmix64(cfg.seed + t as u64);
+            handles.push(std::thread::spawn(move || {
+                for _ in 0..per {
+                    let k = (rnd() % keys as u64) as usize;
+                    let r = rnd() % 1000;
+                    if r < reads {
+                        let _ = map.get(&k);
+                    } else {
+                        let mut e = map.entry(k).or_insert(0);
+                        *e += 1;
+                    }
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        if rep >= cfg.warmup {
+            durations.push(start.elapsed().as_millis());
+        }
+    }
+    let out = make_result(cfg, "dashmap", "siphash", per * cfg.threads, &durations, 0, 0);
+    println!("{}", serde_json::to_string(&out).unwrap());
+}
+
+#[cfg(not(feature = "dashmap"))]
+fn run_dashmap(_cfg: &RunConfig) {
+    eprintln!("--model dashmap requires building with `--features dashmap`");
+    std::process::exit(1);
+}
+
+// Advances `pos` forward to just past the next `\n` in `data`, or to
+// `data.len()` if there is none. Used to align thread byte ranges onto line
+// boundaries so no line is split across two threads.
+#[cfg(feature = "mmap")]
+fn align_to_next_line(data: &[u8], pos: usize) -> usize {
+    match memchr::memchr(b'\n', &data[pos..]) {
+        Some(off) => pos + off + 1,
+        None => data.len(),
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn line_ranges(data: &[u8], threads: usize) -> Vec<(usize, usize)> {
+    let len = data.len();
+    let chunk = len / threads;
+    let mut ranges = Vec::with_capacity(threads);
+    let mut start = 0usize;
+    for t in 0..threads {
+        let end = if t == threads - 1 {
+            len
+        } else {
+            align_to_next_line(data, (t + 1) * chunk)
+        };
+        ranges.push((start, end));
+        start = end;
+    }
+    ranges
+}
+
+// Counts the lines the worker loop in `run_file_driven` actually performs a
+// get/insert for, i.e. every newline-delimited segment except the empty
+// ones, which the worker `continue`s past without touching the map.
+#[cfg(feature = "mmap")]
+fn count_nonblank_lines(data: &[u8]) -> usize {
+    let mut count = 0;
+    let mut line_start = 0usize;
+    for pos in memchr::memchr_iter(b'\n', data) {
+        if pos > line_start {
+            count += 1;
+        }
+        line_start = pos + 1;
+    }
+    if line_start < data.len() {
+        count += 1;
+    }
+    count
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod line_splitting_tests {
+    use super::{count_nonblank_lines, line_ranges};
+
+    #[test]
+    fn line_ranges_does_not_split_a_line_across_a_chunk_boundary() {
+        // len=12, threads=2 puts the naive chunk boundary at byte 6, which
+        // falls in the middle of the "bbb\n" line (bytes 4..8).
+        let data = b"aaa\nbbb\nccc\n";
+        let ranges = line_ranges(data, 2);
+        assert_eq!(ranges, vec![(0, 8), (8, 12)]);
+        for &(start, end) in &ranges {
+            assert!(start == 0 || data[start - 1] == b'\n');
+            assert!(end == data.len() || data[end - 1] == b'\n');
+        }
+    }
+
+    #[test]
+    fn count_nonblank_lines_handles_missing_trailing_newline() {
+        assert_eq!(count_nonblank_lines(b"a\nb\nc"), 3);
+    }
+
+    #[test]
+    fn count_nonblank_lines_skips_blank_lines() {
+        assert_eq!(count_nonblank_lines(b"1\n2\n\n3\n\n\n4\n"), 4);
+    }
+
+    #[test]
+    fn count_nonblank_lines_of_empty_input_is_zero() {
+        assert_eq!(count_nonblank_lines(b""), 0);
+    }
+}
+
+// Parses a line as a `usize` key; falls back to hashing the raw bytes for
+// non-numeric (string) keys, since the shard maps are keyed by `usize`.
+#[cfg(feature = "mmap")]
+fn parse_line_key(line: &[u8]) -> usize {
+    if let Ok(s) = std::str::from_utf8(line) {
+        if let Ok(n) = s.trim().parse::<usize>() {
+            return n;
+        }
+    }
+    use std::hash::{Hash, Hasher};
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    line.hash(&mut h);
+    h.finish() as usize
+}
+
+// File-driven keyspace mode (`--input`): memory-maps `path` once, splits it
+// into `threads` contiguous, newline-aligned byte ranges, and has each
+// thread scan its range with `memchr` for line boundaries, feeding the
+// parsed keys into the same per-shard Mutex<HashMap> get/insert logic as
+// `run_sharded_mutex`. Only built when the `mmap` feature is enabled.
+#[cfg(feature = "mmap")]
+fn run_file_driven<S: BuildHasher + Default + Send + Sync + 'static>(cfg: &RunConfig) {
+    let path = cfg.input_path.as_deref().expect("run_file_driven requires --input");
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("failed to open --input {}: {}", path, e);
+            std::process::exit(1);
+        }
     };
+    let mmap = match unsafe { Mmap::map(&file) } {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("failed to mmap --input {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    let data: &[u8] = &mmap;
+    let ranges = line_ranges(data, cfg.threads);
+    let total_lines = count_nonblank_lines(data);
+
+    let shard_bits = cfg.shards.trailing_zeros();
+    let mut durations = Vec::with_capacity(cfg.runs);
+    for rep in 0..(cfg.warmup + cfg.runs) {
+        let mut maps: Vec<ShardedMutexMap<S>> = Vec::with_capacity(cfg.shards);
+        for _ in 0..cfg.shards {
+            maps.push(Arc::new(CacheAligned(Mutex::new(
+                HashMap::with_hasher(S::default()),
+            ))));
+        }
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            for (t, &(range_start, range_end)) in ranges.iter().enumerate() {
+                let maps = maps.clone();
+                let slice = &data[range_start..range_end];
+                let reads = (cfg.read_ratio * 1000.0) as u64;
+                let mut rnd = splitmix64(cfg.seed + t as u64);
+                scope.spawn(move || {
+                    let mut line_start = 0usize;
+                    for pos in memchr::memchr_iter(b'\n', slice) {
+                        let line = &slice[line_start..pos];
+                        line_start = pos + 1;
+                        if line.is_empty() {
+                            continue;
+                        }
+                        let k = parse_line_key(line);
+                        let r = rnd() % 1000;
+                        let sidx = shard_of(hash_key(k), shard_bits);
+                        let m = &maps[sidx];
+                        if r < reads {
+                            let g = m.lock().unwrap();
+                            let _ = g.get(&k);
+                        } else {
+                            let mut g = m.lock().unwrap();
+                            let e = g.entry(k).or_insert(0);
+                            *e += 1;
+                        }
+                    }
+                    if line_start < slice.len() {
+                        let line = &slice[line_start..];
+                        let k = parse_line_key(line);
+                        let r = rnd() % 1000;
+                        let sidx = shard_of(hash_key(k), shard_bits);
+                        let m = &maps[sidx];
+                        if r < reads {
+                            let g = m.lock().unwrap();
+                            let _ = g.get(&k);
+                        } else {
+                            let mut g = m.lock().unwrap();
+                            let e = g.entry(k).or_insert(0);
+                            *e += 1;
+                        }
+                    }
+                });
+            }
+        });
+        if rep >= cfg.warmup {
+            durations.push(start.elapsed().as_millis());
+        }
+    }
+    let out = make_result(
+        cfg,
+        "threads-sharded",
+        &cfg.hasher_name,
+        total_lines,
+        &durations,
+        64,
+        total_lines,
+    );
     println!("{}", serde_json::to_string(&out).unwrap());
 }
 
+// `S` is unused here (the real, mmap-gated implementation above is the one
+// that needs it) but kept so callers don't need a separate cfg-gated
+// turbofish depending on whether `mmap` is enabled.
+#[cfg(not(feature = "mmap"))]
+#[allow(clippy::extra_unused_type_parameters)]
+fn run_file_driven<S: BuildHasher + Default + Send + Sync + 'static>(_cfg: &RunConfig) {
+    eprintln!("--input requires building with `--features mmap`");
+    std::process::exit(1);
+}
+
 fn rustc_version_runtime() -> String {
     // Best effort: read env var set by cargo; else unknown
     option_env!("RUSTC_VERSION")
@@ -121,8 +623,12 @@ fn main() {
     let mut keys = 100_000usize;
     let mut read_ratio = 0.9f64;
     let mut seed = 42u64;
-    let mut _model = "threads".to_string();
-    let mut shards = 64usize;
+    let mut model = "threads-sharded".to_string();
+    let mut shards: Option<usize> = None;
+    let mut hasher = "siphash".to_string();
+    let mut warmup = 0usize;
+    let mut runs = 1usize;
+    let mut input: Option<String> = None;
     let args: Vec<String> = std::env::args().collect();
     let mut i = 1;
     while i < args.len() {
@@ -149,16 +655,184 @@ fn main() {
             }
             "--model" => {
                 i += 1;
-                _model = args[i].clone();
+                model = args[i].clone();
             }
             "--shards" => {
                 i += 1;
-                shards = args[i].parse().unwrap();
+                shards = Some(args[i].parse().unwrap());
+            }
+            "--hasher" => {
+                i += 1;
+                hasher = args[i].clone();
+            }
+            "--warmup" => {
+                i += 1;
+                warmup = args[i].parse().unwrap();
+            }
+            "--runs" => {
+                i += 1;
+                runs = args[i].parse().unwrap();
+                if runs == 0 {
+                    eprintln!("--runs must be at least 1");
+                    std::process::exit(1);
+                }
+            }
+            "--input" => {
+                i += 1;
+                input = Some(args[i].clone());
             }
             _ => {}
         }
         i += 1;
     }
-    // For fairness use sharded Mutex<HashMap>; you can switch to DashMap by feature
-    run_sharded(threads, iterations, keys, read_ratio, seed, shards);
+    let cfg = RunConfig {
+        model,
+        threads,
+        iterations,
+        keys,
+        read_ratio,
+        seed,
+        shards: effective_shard_count(shards),
+        hasher_name: hasher,
+        warmup,
+        runs,
+        input_path: input,
+    };
+    if cfg.input_path.is_some() && cfg.model != "threads-sharded" {
+        eprintln!(
+            "--model '{}' is not supported together with --input; file-driven mode only implements the threads-sharded backend",
+            cfg.model
+        );
+        std::process::exit(1);
+    }
+    if cfg.input_path.is_some() {
+        run_with_hasher(&cfg, |cfg| match cfg.hasher_name.as_str() {
+            "siphash" => run_file_driven::<std::collections::hash_map::RandomState>(cfg),
+            #[cfg(feature = "fxhash")]
+            "fxhash" => run_file_driven::<FxBuildHasher>(cfg),
+            #[cfg(feature = "ahash")]
+            "ahash" => run_file_driven::<AHashState>(cfg),
+            _ => unreachable!(),
+        });
+        return;
+    }
+    run_with_hasher(&cfg, dispatch);
+}
+
+// Checks that `cfg.hasher_name` is one of siphash/fxhash/ahash and that its
+// cargo feature is built in (exiting with an error otherwise), then calls
+// `run(cfg)`. Shared between the normal and file-driven (`--input`) entry
+// points in `main`; mapping the name to a concrete `BuildHasher` type is left
+// to `run`, so that match is written in each caller.
+fn run_with_hasher<F>(cfg: &RunConfig, run: F)
+where
+    F: for<'a> Fn(&'a RunConfig),
+{
+    match cfg.hasher_name.as_str() {
+        "siphash" | "fxhash" | "ahash" => {}
+        other => {
+            eprintln!(
+                "unknown --hasher '{}': expected one of siphash, fxhash, ahash",
+                other
+            );
+            std::process::exit(1);
+        }
+    }
+    #[cfg(not(feature = "fxhash"))]
+    if cfg.hasher_name == "fxhash" {
+        eprintln!("--hasher fxhash requires building with `--features fxhash`");
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "ahash"))]
+    if cfg.hasher_name == "ahash" {
+        eprintln!("--hasher ahash requires building with `--features ahash`");
+        std::process::exit(1);
+    }
+    run(cfg);
+}
+
+// Resolves `cfg.hasher_name` to a per-map `BuildHasher` `S` and hands off to
+// `dispatch_model::<S>`, which picks the backend (`--model`).
+// `global-mutex` and `dashmap` always hash with the std `RandomState`, since
+// `--hasher` only applies to the sharded models (see chunk0-3).
+fn dispatch(cfg: &RunConfig) {
+    match cfg.hasher_name.as_str() {
+        "siphash" => dispatch_model::<std::collections::hash_map::RandomState>(cfg),
+        #[cfg(feature = "fxhash")]
+        "fxhash" => dispatch_model::<FxBuildHasher>(cfg),
+        #[cfg(feature = "ahash")]
+        "ahash" => dispatch_model::<AHashState>(cfg),
+        _ => unreachable!("validated by run_with_hasher"),
+    }
+}
+
+fn dispatch_model<S: BuildHasher + Default + Send + Sync + 'static>(cfg: &RunConfig) {
+    match cfg.model.as_str() {
+        "global-mutex" => run_global_mutex(cfg),
+        "threads-sharded" => run_sharded_mutex::<S>(cfg),
+        "sharded-rwlock" => run_sharded_rwlock::<S>(cfg),
+        "dashmap" => run_dashmap(cfg),
+        other => {
+            eprintln!(
+                "unknown --model '{}': expected one of global-mutex, threads-sharded, sharded-rwlock, dashmap",
+                other
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod duration_stats_tests {
+    use super::duration_stats;
+
+    #[test]
+    fn single_sample_reports_that_sample_for_every_stat() {
+        let (min, mean, max, p50, p99) = duration_stats(&[5]);
+        assert_eq!((min, mean, max, p50, p99), (5, 5.0, 5, 5, 5));
+    }
+
+    #[test]
+    fn multi_sample_percentiles_match_hand_computed_values() {
+        let (min, mean, max, p50, p99) = duration_stats(&[10, 30, 20, 50, 40]);
+        assert_eq!(min, 10);
+        assert_eq!(mean, 30.0);
+        assert_eq!(max, 50);
+        assert_eq!(p50, 30);
+        assert_eq!(p99, 50);
+    }
+}
+
+#[cfg(test)]
+mod sharding_tests {
+    use super::{effective_shard_count, shard_of};
+
+    #[test]
+    fn shard_of_with_zero_bits_is_always_shard_zero() {
+        assert_eq!(shard_of(0, 0), 0);
+        assert_eq!(shard_of(u64::MAX, 0), 0);
+    }
+
+    #[test]
+    fn shard_of_picks_top_bits() {
+        // shard_bits=2 means the top 2 bits of the hash select among 4 shards.
+        assert_eq!(shard_of(0, 2), 0);
+        assert_eq!(shard_of(1u64 << 63, 2), 2);
+        assert_eq!(shard_of(u64::MAX, 2), 3);
+    }
+
+    #[test]
+    fn effective_shard_count_rounds_requested_up_to_power_of_two() {
+        assert_eq!(effective_shard_count(Some(1)), 1);
+        assert_eq!(effective_shard_count(Some(4)), 4);
+        assert_eq!(effective_shard_count(Some(5)), 8);
+        assert_eq!(effective_shard_count(Some(9)), 16);
+    }
+
+    #[test]
+    fn effective_shard_count_default_is_a_power_of_two() {
+        let n = effective_shard_count(None);
+        assert!(n.is_power_of_two());
+        assert!(n > 0);
+    }
 }